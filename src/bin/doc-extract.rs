@@ -15,12 +15,16 @@ use anyhow::bail;
 use anyhow::Result;
 use clap::Parser;
 use csv::WriterBuilder;
+use doc_read::cache::{content_hash, Cache};
+use doc_read::index::SearchIndex;
+use doc_read::terms_config::TermsConfig;
 use doc_read::read_header_info;
 
 use doc_read::read_to_info_table;
 use doc_read::read_to_text_starting_with;
 use doc_read::ExtractedInfo;
 use quick_xml::Reader;
+use rayon::prelude::*;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -37,6 +41,46 @@ struct Args {
     data_dir: PathBuf,
     #[clap(default_value = "/tmp/out.csv")]
     output_file: PathBuf,
+    /// Reuse/populate an extraction cache at this path to skip reparsing
+    /// documents whose content has not changed.
+    #[clap(long)]
+    cache: Option<PathBuf>,
+    /// Load header words and section terms from this config file instead of the
+    /// built-in defaults. See `doc_read::terms_config` for the format.
+    #[clap(long)]
+    terms_config: Option<PathBuf>,
+    /// Tolerate up to this many typos per label, counted over the whole label
+    /// and fewer for short labels, when matching header words and section
+    /// labels. 0 (the default) disables typo tolerance; labels still
+    /// match case- and punctuation-insensitively, not byte-for-byte.
+    #[clap(long, short = 't', default_value_t = 0)]
+    max_typos: usize,
+    /// Cap the extraction thread pool at this many workers. Defaults to one per
+    /// available core.
+    #[clap(long, short)]
+    jobs: Option<usize>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Build an inverted search index over the extracted records and write it to
+    /// a JSON sidecar, so later queries don't re-parse the corpus.
+    Index {
+        /// Where to write the serialized index.
+        #[clap(long, default_value = "/tmp/index.json")]
+        output: PathBuf,
+    },
+    /// Query a previously built index, printing matching files and the section
+    /// snippet each query word landed in.
+    Search {
+        /// The index sidecar to query.
+        #[clap(long, default_value = "/tmp/index.json")]
+        index: PathBuf,
+        /// The words to search for.
+        query: String,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
@@ -58,29 +102,185 @@ fn main() -> anyhow::Result<()> {
     let Args {
         data_dir,
         output_file,
+        cache,
+        terms_config,
+        max_typos,
+        jobs,
+        command,
     } = Args::parse();
 
+    doc_read::matcher::set_max_typos(max_typos);
+    if let Some(jobs) = jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()?;
+    }
+
+    // The search subcommand works entirely off the sidecar; it never touches the
+    // corpus or the cache.
+    if let Some(Command::Search { index, query }) = &command {
+        let index = SearchIndex::load(index)?;
+        for result in index.search(query) {
+            println!(
+                "{} ({} terms, {} fields)",
+                result.file, result.matched_terms, result.matched_fields
+            );
+            for (field, snippet) in result.snippets {
+                println!("  {field}: {snippet}");
+            }
+        }
+        return Ok(());
+    }
+
+    let cache = match cache {
+        Some(path) => Some(Cache::open(path)?),
+        None => None,
+    };
+
+    let terms = match &terms_config {
+        Some(path) => {
+            let config = TermsConfig::load(path)?;
+            info!(
+                "Loaded {} header words and {} terms from {path:?}",
+                config.headers.len(),
+                config.terms.len()
+            );
+            config
+        }
+        None => TermsConfig::default(),
+    };
+    doc_read::warn_unrecognised_config(&terms);
+
     info!("Parsing docx files...");
-    let files = collect_doc_xmls(&data_dir)?;
-    info!("Found {} docx files", files.len());
-    let mut wtr = WriterBuilder::new()
-        .has_headers(true)
-        .double_quote(true)
-        .from_path(output_file)?;
-    wtr.write_record(ExtractedInfo::header_record())?;
-    for (file, file_path) in files {
-        info!("Processing file: {file_path:?}");
-        match extract_one(&file, file_path.clone()) {
-            Ok(extracted) => wtr.write_record(extracted.as_record())?,
-            Err(e) => {
-                error!("{e:?} in file: {file_path:?}");
+    let paths = collect_doc_paths(&data_dir)?;
+    info!("Found {} docx files", paths.len());
+    let records = extract_records(paths, cache.as_ref(), &terms);
+
+    match command {
+        Some(Command::Index { output }) => {
+            info!("Indexing {} records into {output:?}", records.len());
+            SearchIndex::build(&records).save(&output)?;
+        }
+        // No subcommand (or any handled above): the default flat-CSV extraction.
+        _ => {
+            let mut wtr = WriterBuilder::new()
+                .has_headers(true)
+                .double_quote(true)
+                .from_path(output_file)?;
+            wtr.write_record(ExtractedInfo::header_record())?;
+            for record in &records {
+                wtr.write_record(record.as_record())?;
             }
         }
     }
     Ok(())
 }
 
-fn extract_one(doc: &[u8], file: PathBuf) -> Result<ExtractedInfo> {
+/// Extract every collected document, logging and skipping per-file failures so
+/// one bad document cannot abort the batch. Both the zip read/inflate and the
+/// XML parse run across rayon's pool; results are reassembled in the original
+/// path order so the CSV is deterministic regardless of which worker finished
+/// first.
+fn extract_records(
+    paths: Vec<PathBuf>,
+    cache: Option<&Cache>,
+    terms: &TermsConfig,
+) -> Vec<ExtractedInfo> {
+    // Without a cache there is nothing that has to stay on one thread, so read
+    // and parse each document in a single parallel pass.
+    let Some(cache) = cache else {
+        let parsed: Vec<(PathBuf, Result<ExtractedInfo>)> = paths
+            .into_par_iter()
+            .map(|path| {
+                info!("Processing file: {path:?}");
+                let info = read_and_parse(&path, terms);
+                (path, info)
+            })
+            .collect();
+        return parsed
+            .into_iter()
+            .filter_map(|(path, info)| match info {
+                Ok(extracted) => Some(extracted),
+                Err(e) => {
+                    error!("{e:?} in file: {path:?}");
+                    None
+                }
+            })
+            .collect();
+    };
+
+    // With a cache, read the documents in parallel (the slow I/O + inflate step)
+    // but keep cache lookups and writes on this thread: SQLite's connection
+    // handle isn't `Sync`. Each work item carries its slot index so the output
+    // stays in path order.
+    let docs: Vec<(usize, PathBuf, Result<Vec<u8>>)> = paths
+        .into_par_iter()
+        .enumerate()
+        .map(|(idx, path)| {
+            let bytes = read_document_xml(&path);
+            (idx, path, bytes)
+        })
+        .collect();
+
+    let mut slots: Vec<Option<ExtractedInfo>> = (0..docs.len()).map(|_| None).collect();
+    let mut to_parse: Vec<(usize, Vec<u8>, PathBuf, String)> = vec![];
+    for (idx, path, bytes) in docs {
+        let doc = match bytes {
+            Ok(doc) => doc,
+            Err(e) => {
+                error!("{e:?} in file: {path:?}");
+                continue;
+            }
+        };
+        let hash = content_hash(&doc);
+        match cache.get(&hash) {
+            Ok(Some(cached)) => {
+                debug!("Serving {path:?} from cache");
+                slots[idx] = Some(cached);
+            }
+            Ok(None) => to_parse.push((idx, doc, path, hash)),
+            Err(e) => {
+                error!("{e:?} reading cache for {path:?}");
+                to_parse.push((idx, doc, path, hash));
+            }
+        }
+    }
+
+    // Parse the cache misses in parallel, carrying each slot's index through.
+    let parsed: Vec<(usize, PathBuf, String, Result<ExtractedInfo>)> = to_parse
+        .into_par_iter()
+        .map(|(idx, doc, path, hash)| {
+            info!("Processing file: {path:?}");
+            let info = parse_one(&doc, path.clone(), terms);
+            (idx, path, hash, info)
+        })
+        .collect();
+
+    // Fold the results back in, writing fresh extractions to the cache and
+    // logging+skipping per-file failures.
+    for (idx, path, hash, info) in parsed {
+        match info {
+            Ok(extracted) => {
+                if let Err(e) = cache.put(&extracted.file, &hash, &extracted) {
+                    error!("{e:?} writing cache for {path:?}");
+                }
+                slots[idx] = Some(extracted);
+            }
+            Err(e) => error!("{e:?} in file: {path:?}"),
+        }
+    }
+
+    slots.into_iter().flatten().collect()
+}
+
+/// Read a document's `word/document.xml` and parse it in one step, for the
+/// cacheless path.
+fn read_and_parse(path: &Path, terms: &TermsConfig) -> Result<ExtractedInfo> {
+    let doc = read_document_xml(path)?;
+    parse_one(&doc, path.to_path_buf(), terms)
+}
+
+fn parse_one(doc: &[u8], file: PathBuf, terms: &TermsConfig) -> Result<ExtractedInfo> {
     let mut reader = Reader::from_reader(doc);
 
     let config = reader.config_mut();
@@ -88,11 +288,11 @@ fn extract_one(doc: &[u8], file: PathBuf) -> Result<ExtractedInfo> {
     config.trim_text(true);
     let mut buf = vec![];
     read_to_info_table(&mut buf, &mut reader)?;
-    let info = read_header_info(&mut buf, &mut reader)?;
+    let info = read_header_info(&mut buf, &mut reader, terms)?;
 
     // read_to_text_starting_with(TEXT_STARTING_WITH, &mut buf, &mut reader)?;
     debug!("Reading areas_that_require_intervention_and_support");
-    let secg = read_sectiong_info(&mut buf, &mut reader)?;
+    let secg = read_sectiong_info(&mut buf, &mut reader, terms)?;
 
     Ok(ExtractedInfo {
         header: info,
@@ -101,24 +301,26 @@ fn extract_one(doc: &[u8], file: PathBuf) -> Result<ExtractedInfo> {
     })
 }
 
-fn collect_doc_xmls(dir_with_files: &Path) -> anyhow::Result<Vec<(Vec<u8>, PathBuf)>> {
-    let mut ret = vec![];
-    for f in WalkDir::new(dir_with_files)
+/// Read the `word/document.xml` entry out of a `.docx` zip into memory.
+fn read_document_xml(path: &Path) -> Result<Vec<u8>> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    debug!(
+        "Zip files in {path:?}: {:?}",
+        zip.file_names().collect::<Vec<_>>()
+    );
+    let mut file = zip.by_name("word/document.xml")?;
+    let mut buf = Vec::with_capacity(file.size().try_into().unwrap());
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn collect_doc_paths(dir_with_files: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let ret: Vec<PathBuf> = WalkDir::new(dir_with_files)
         .into_iter()
         .filter_map(|x| x.ok())
         .filter(|x| x.path().extension() == Some(OsStr::new("docx")))
-    {
-        let mut zip = ZipArchive::new(File::open(f.path())?)?;
-        debug!(
-            "Zip files in {f:?}: {:?}",
-            zip.file_names().collect::<Vec<_>>()
-        );
-        let mut file = zip.by_name("word/document.xml")?;
-
-        let mut buf = Vec::with_capacity(file.size().try_into().unwrap());
-        file.read_to_end(&mut buf)?;
-        ret.push((buf, f.path().to_path_buf()));
-    }
+        .map(|x| x.path().to_path_buf())
+        .collect();
     if ret.is_empty() {
         bail!(
             "No docx files found in {:?}",
@@ -7,7 +7,9 @@ use std::path::PathBuf;
 
 use anyhow::bail;
 use anyhow::Result;
+use clap::Parser;
 use csv::WriterBuilder;
+use doc_read::terms_config::TermsConfig;
 use doc_read::read_header_info;
 use doc_read::read_part_four;
 use doc_read::read_part_four_no_search;
@@ -16,13 +18,34 @@ use doc_read::read_to_text_starting_with;
 use doc_read::ExtractedInfo;
 use doc_read::Part4;
 use quick_xml::Reader;
+use rayon::prelude::*;
 use tracing::debug;
+use tracing::error;
 use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::prelude::__tracing_subscriber_SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use walkdir::WalkDir;
 use zip::ZipArchive;
 
+#[derive(clap::Parser)]
+#[clap(about = "Extracts data from word files in a directory")]
+struct Args {
+    /// Load header words and section terms from this config file instead of the
+    /// built-in defaults. See `doc_read::terms_config` for the format.
+    #[clap(long)]
+    terms_config: Option<PathBuf>,
+    /// Cap the extraction thread pool at this many workers. Defaults to one per
+    /// available core.
+    #[clap(long, short)]
+    jobs: Option<usize>,
+    /// Tolerate up to this many typos per label, counted over the whole label
+    /// and fewer for short labels, when matching header words and section
+    /// labels. 0 (the default) disables typo tolerance; labels still
+    /// match case- and punctuation-insensitively, not byte-for-byte.
+    #[clap(long, short = 't', default_value_t = 0)]
+    max_typos: usize,
+}
+
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::registry()
         .with(
@@ -31,32 +54,73 @@ fn main() -> anyhow::Result<()> {
         )
         .with(tracing_subscriber::fmt::layer().with_span_events(FmtSpan::CLOSE))
         .init();
-    let files = collect_doc_xmls(Path::new("../../data/"))?;
+    let Args {
+        terms_config,
+        jobs,
+        max_typos,
+    } = Args::parse();
+    doc_read::matcher::set_max_typos(max_typos);
+    let terms = match terms_config {
+        Some(path) => TermsConfig::load(path)?,
+        None => TermsConfig::default(),
+    };
+    debug!(
+        "Loaded {} header words and {} terms from config",
+        terms.headers.len(),
+        terms.terms.len()
+    );
+    doc_read::warn_unrecognised_config(&terms);
+    if let Some(jobs) = jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()?;
+    }
+
+    let paths = collect_doc_paths(Path::new("../../data/"))?;
+
+    // Parse every document in parallel; rayon's indexed collect keeps the
+    // results in the original path order so the CSV is deterministic across
+    // runs regardless of which worker finished first.
+    let extracted: Vec<(PathBuf, Result<ExtractedInfo>)> = paths
+        .into_par_iter()
+        .map(|path| {
+            let info = extract_one(&path, &terms);
+            (path, info)
+        })
+        .collect();
+
     let mut wtr = WriterBuilder::new()
         .has_headers(true)
         .double_quote(true)
         .from_path("/tmp/out.csv")?;
     let mut header_written = false;
-    for (file, file_path) in files {
-        let extracted = extract_one(&file, file_path)?;
-        if !header_written {
-            wtr.write_record(extracted.header_record())?;
-            header_written = true;
+    for (path, info) in extracted {
+        match info {
+            Ok(extracted) => {
+                if !header_written {
+                    wtr.write_record(extracted.header_record())?;
+                    header_written = true;
+                }
+                wtr.write_record(extracted.as_record())?;
+            }
+            // Isolate per-file failures so one bad document cannot abort the
+            // whole batch.
+            Err(e) => error!("{e:?} in file: {path:?}"),
         }
-        wtr.write_record(extracted.as_record())?;
     }
     Ok(())
 }
 
-fn extract_one(doc: &[u8], file: PathBuf) -> Result<ExtractedInfo> {
-    let mut reader = Reader::from_reader(doc);
+fn extract_one(file: &Path, terms: &TermsConfig) -> Result<ExtractedInfo> {
+    let doc = read_document_xml(file)?;
+    let mut reader = Reader::from_reader(doc.as_slice());
 
     let config = reader.config_mut();
 
     config.trim_text(true);
     let mut buf = vec![];
     read_to_info_table(&mut buf, &mut reader)?;
-    let info = read_header_info(&mut buf, &mut reader)?;
+    let info = read_header_info(&mut buf, &mut reader, terms)?;
 
     read_to_text_starting_with(b"PART 4:", &mut buf, &mut reader)?;
 
@@ -96,28 +160,30 @@ fn extract_one(doc: &[u8], file: PathBuf) -> Result<ExtractedInfo> {
     Ok(ExtractedInfo {
         header: info,
         part4: p4,
-        file,
+        file: file.to_path_buf(),
     })
 }
 
-fn collect_doc_xmls(dir_with_files: &Path) -> anyhow::Result<Vec<(Vec<u8>, PathBuf)>> {
-    let mut ret = vec![];
-    for f in WalkDir::new(dir_with_files)
+/// Read the `word/document.xml` entry out of a `.docx` zip into memory.
+fn read_document_xml(path: &Path) -> Result<Vec<u8>> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    debug!(
+        "Zip files in {path:?}: {:?}",
+        zip.file_names().collect::<Vec<_>>()
+    );
+    let mut file = zip.by_name("word/document.xml")?;
+    let mut buf = Vec::with_capacity(file.size().try_into().unwrap());
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn collect_doc_paths(dir_with_files: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let ret: Vec<PathBuf> = WalkDir::new(dir_with_files)
         .into_iter()
         .filter_map(|x| x.ok())
         .filter(|x| x.path().extension() == Some(OsStr::new("docx")))
-    {
-        let mut zip = ZipArchive::new(File::open(f.path())?)?;
-        debug!(
-            "Zip files in {f:?}: {:?}",
-            zip.file_names().collect::<Vec<_>>()
-        );
-        let mut file = zip.by_name("word/document.xml")?;
-
-        let mut buf = Vec::with_capacity(file.size().try_into().unwrap());
-        file.read_to_end(&mut buf)?;
-        ret.push((buf, f.path().to_path_buf()));
-    }
+        .map(|x| x.path().to_path_buf())
+        .collect();
     if ret.is_empty() {
         bail!(
             "No docx files found in {:?}",
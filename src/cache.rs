@@ -0,0 +1,142 @@
+//! Optional extraction cache.
+//!
+//! Re-running the extractor over a directory otherwise reparses every `.docx`
+//! on each pass. This keeps a small SQLite database keyed by a SHA-256 of the
+//! raw document bytes so unchanged files can be served from the stored JSON
+//! instead of walking the XML again.
+
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::ExtractedInfo;
+
+/// Hex SHA-256 of a document's `word/document.xml` payload — the bytes the
+/// extractor actually parses — used as the cache key. (The surrounding `.docx`
+/// zip also carries styles and metadata that don't affect extraction, so keying
+/// on the inner XML treats two zips with identical document bodies as the same
+/// input. The entry is still read and inflated to compute the hash; a hit skips
+/// only the XML parse.)
+pub fn content_hash(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// A reusable on-disk cache of previously extracted documents.
+pub struct Cache {
+    conn: Connection,
+}
+
+impl Cache {
+    /// Open (creating if needed) the cache database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS extraction_cache (
+                content_hash   TEXT NOT NULL PRIMARY KEY,
+                file_path      TEXT NOT NULL,
+                extracted_json TEXT NOT NULL,
+                extracted_at   TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Return the cached [`ExtractedInfo`] for `hash`, if one was stored.
+    pub fn get(&self, hash: &str) -> Result<Option<ExtractedInfo>> {
+        let json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT extracted_json FROM extraction_cache WHERE content_hash = ?1",
+                params![hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match json {
+            Some(json) => {
+                debug!("Cache hit for {hash}");
+                Ok(Some(serde_json::from_str(&json)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Upsert the freshly extracted info for a document.
+    pub fn put(&self, file_path: &Path, hash: &str, info: &ExtractedInfo) -> Result<()> {
+        let json = serde_json::to_string(info)?;
+        self.conn.execute(
+            "INSERT INTO extraction_cache (content_hash, file_path, extracted_json)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(content_hash) DO UPDATE SET
+                file_path = excluded.file_path,
+                extracted_json = excluded.extracted_json,
+                extracted_at = CURRENT_TIMESTAMP",
+            params![hash, file_path.to_str().unwrap_or_default(), json],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::{HeaderInfo, SectionG};
+
+    fn sample(school: &str) -> ExtractedInfo {
+        ExtractedInfo {
+            header: HeaderInfo {
+                province: "Province".to_string(),
+                district: "District".to_string(),
+                school: school.to_string(),
+                subject: None,
+            },
+            sectiong: SectionG {
+                areas_that_require_intervention_and_support: "areas".to_string(),
+                recommendations: "recs".to_string(),
+            },
+            file: PathBuf::from("report.docx"),
+        }
+    }
+
+    #[test]
+    fn put_then_get_roundtrips() {
+        let cache = Cache::open(":memory:").unwrap();
+        let info = sample("Greenwood");
+        cache.put(&info.file, "abc", &info).unwrap();
+        let got = cache.get("abc").unwrap().expect("cache hit");
+        assert_eq!(got.header.school, "Greenwood");
+    }
+
+    #[test]
+    fn get_miss_returns_none() {
+        let cache = Cache::open(":memory:").unwrap();
+        assert!(cache.get("no-such-hash").unwrap().is_none());
+    }
+
+    #[test]
+    fn put_upserts_in_place_on_conflict() {
+        let cache = Cache::open(":memory:").unwrap();
+        cache.put(Path::new("a.docx"), "key", &sample("Old")).unwrap();
+        cache.put(Path::new("a.docx"), "key", &sample("New")).unwrap();
+
+        let got = cache.get("key").unwrap().unwrap();
+        assert_eq!(got.header.school, "New");
+        // The ON CONFLICT clause updates the row rather than inserting a second.
+        let rows: i64 = cache
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM extraction_cache WHERE content_hash = ?1",
+                params!["key"],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+}
@@ -1,6 +1,8 @@
+use anyhow::Result;
 use tracing::debug;
 
 use crate::extraction::ExtractedInfo;
+use crate::matcher::{DifferenceMatcher, IncludeMatcher, Matcher, NormalizedMatcher, UnionMatcher};
 use std::borrow::Cow;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -15,211 +17,337 @@ impl ExtractedInfo {
         record
     }
 
-    pub fn header_record() -> Vec<&'static str> {
+    pub fn header_record(terms: &[Term]) -> Vec<String> {
         let mut ret = vec![];
-        for term in EXTRACT_SEARCH_TERMS_IN_ORDER {
-            ret.push(term.as_main());
+        for term in terms {
+            ret.push(term.as_main().to_string());
         }
 
-        ret.push("File");
+        ret.push("File".to_string());
         ret
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
-pub enum Term {
-    Single(&'static str),
+/// The accepted wordings of a term: a canonical `main` plus any number of
+/// alternates. This is the include side of a term; exclusions live on [`Term`].
+#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+pub enum TermKind {
+    Single(Cow<'static, str>),
     Double {
-        main: &'static str,
-        alt: &'static str,
+        main: Cow<'static, str>,
+        alt: Cow<'static, str>,
     },
     Many {
-        main: &'static str,
-        other: &'static [&'static str],
+        main: Cow<'static, str>,
+        other: Vec<Cow<'static, str>>,
     },
 }
 
+/// A search term: the wordings that should match it (`kind`) minus the wordings
+/// that look like it but must not (`exclude`), e.g. `RECOMMENDATIONS` while
+/// excluding `RECOMMENDATIONS TABLE OF CONTENTS`.
+///
+/// Matching delegates to a compiled [`crate::matcher`]: the alternates become a
+/// [`UnionMatcher`] and the exclusions subtract from it through a
+/// [`DifferenceMatcher`], so section detection is expressed compositionally
+/// instead of by hand-rolling the fan-out here.
+#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+pub struct Term {
+    kind: TermKind,
+    /// Operator-supplied glob/regex include patterns, compiled lazily into
+    /// [`IncludeMatcher`]s alongside the literal wordings.
+    patterns: Vec<Cow<'static, str>>,
+    exclude: Vec<Cow<'static, str>>,
+}
+
+/// Compile one operator pattern. A `glob:` prefix selects the anchored shell
+/// glob syntax; anything else is treated as a regex.
+fn compile_pattern(pattern: &str) -> Result<Box<dyn Matcher>> {
+    match pattern.strip_prefix("glob:") {
+        Some(glob) => Ok(Box::new(IncludeMatcher::glob(glob)?)),
+        None => Ok(Box::new(IncludeMatcher::regex(pattern)?)),
+    }
+}
+
 impl Display for Term {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Single(s) => write!(f, "{s}"),
-            Self::Double { main, .. } => write!(f, "{main}"),
-            Self::Many { main, .. } => write!(f, "{main}"),
-        }
+        write!(f, "{}", self.as_main())
     }
 }
 
 impl PartialEq<str> for Term {
     fn eq(&self, other: &str) -> bool {
-        match self {
-            Self::Single(s) => *s == other,
-            Self::Double { main, alt } => *main == other || *alt == other,
-            Self::Many { main, other: o } => *main == other || o.contains(&other),
+        match &self.kind {
+            TermKind::Single(s) => s.as_ref() == other,
+            TermKind::Double { main, alt } => main.as_ref() == other || alt.as_ref() == other,
+            TermKind::Many { main, other: o } => {
+                main.as_ref() == other || o.iter().any(|t| t.as_ref() == other)
+            }
         }
     }
 }
 
 impl Term {
-    pub fn as_main(&self) -> &'static str {
-        match self {
-            Self::Single(s) => s,
-            Self::Double { main, .. } => main,
-            Self::Many { main, .. } => main,
+    /// Every accepted wording, canonical one first. These are the include
+    /// patterns that get compiled into the term's [`UnionMatcher`].
+    fn wordings(&self) -> Vec<&str> {
+        match &self.kind {
+            TermKind::Single(s) => vec![s.as_ref()],
+            TermKind::Double { main, alt } => vec![main.as_ref(), alt.as_ref()],
+            TermKind::Many { main, other } => {
+                let mut ret = vec![main.as_ref()];
+                ret.extend(other.iter().map(Cow::as_ref));
+                ret
+            }
         }
     }
 
-    pub fn matches(&self, search_space: &str) -> bool {
-        match self {
-            Self::Single(s) => {
-                if search_space.trim() == *s {
-                    return true;
-                }
-                deep_search_term(search_space, s)
-            }
-            Self::Double { main, alt } => {
-                search_space.trim() == *main
-                    || search_space.trim() == *alt
-                    || deep_search_term(search_space, main)
-                    || deep_search_term(search_space, alt)
-            }
-            Self::Many { main, other } => {
-                if search_space.trim() == *main {
-                    return true;
-                }
-                for term in *other {
-                    if search_space.trim() == *term {
-                        return true;
-                    }
-                }
-                if deep_search_term(search_space, main) {
-                    return true;
-                }
-                for term in *other {
-                    if deep_search_term(search_space, term) {
-                        return true;
-                    }
-                }
-                false
+    /// Compile the include side into a union matcher: every accepted wording as
+    /// a [`NormalizedMatcher`], plus any operator-supplied glob/regex `patterns`
+    /// as [`IncludeMatcher`]s. Patterns that fail to compile are logged and
+    /// skipped so one typo cannot blank a whole column.
+    fn include_matcher(&self) -> UnionMatcher {
+        let mut inner: Vec<Box<dyn Matcher>> = self
+            .wordings()
+            .into_iter()
+            .map(|w| Box::new(NormalizedMatcher::new(w)) as Box<dyn Matcher>)
+            .collect();
+        for pattern in &self.patterns {
+            match compile_pattern(pattern) {
+                Ok(matcher) => inner.push(matcher),
+                Err(e) => debug!("Ignoring unparseable term pattern {pattern:?}: {e}"),
             }
         }
+        UnionMatcher::new(inner)
     }
 
-    pub fn is(&self, main_word: &str) -> bool {
-        match self {
-            Self::Single(s) => *s == main_word,
-            Self::Double { main, .. } => *main == main_word,
-            Self::Many { main, .. } => *main == main_word,
+    /// Compile the exclusion side into a matcher. Empty when the term has no
+    /// exclusions, in which case nothing is ever subtracted.
+    fn exclusion_matcher(&self) -> UnionMatcher {
+        UnionMatcher::new(
+            self.exclude
+                .iter()
+                .map(|e| Box::new(NormalizedMatcher::new(e.as_ref())) as Box<dyn Matcher>)
+                .collect(),
+        )
+    }
+
+    pub fn as_main(&self) -> &str {
+        match &self.kind {
+            TermKind::Single(s) => s,
+            TermKind::Double { main, .. } => main,
+            TermKind::Many { main, .. } => main,
         }
     }
 
+    /// How (if at all) this term matches `search_space`. Exact/normalized
+    /// matches are always reported in preference to fuzzy ones, and any wording
+    /// caught by the term's exclusions is rejected before either tier runs.
+    pub fn matches(&self, search_space: &str) -> Option<MatchStrength> {
+        // Exact/normalized tier: the accepted wordings minus the exclusions.
+        let exact = DifferenceMatcher::new(
+            Box::new(self.include_matcher()),
+            Box::new(self.exclusion_matcher()),
+        );
+        if exact.matches(search_space) {
+            return Some(MatchStrength::Exact);
+        }
+
+        // Exclusions bind the fuzzy tier too: a phrase rejected for this term
+        // must not come back in as a near-miss of one of its wordings.
+        if self.is_excluded(search_space) {
+            return None;
+        }
+        // Fuzzy tier: attribute the typo to the *nearest* accepted wording
+        // rather than the first one declared, so a single best candidate wins
+        // when several are within the budget.
+        self.wordings()
+            .into_iter()
+            .filter_map(|wording| deep_search_term(search_space, wording))
+            .min()
+            .map(|_| MatchStrength::Fuzzy)
+    }
+
+    /// Whether `text` is one of the term's explicit exclusions. Consulted by the
+    /// paragraph path too, so an excluded line never becomes a candidate.
+    fn is_excluded(&self, text: &str) -> bool {
+        self.exclusion_matcher().matches(text)
+    }
+
+    pub fn is(&self, main_word: &str) -> bool {
+        self.as_main() == main_word
+    }
+
     pub fn word_starts_with_term(&self, word: &str) -> bool {
-        match self {
-            Self::Single(s) => word.trim().starts_with(s),
-            Self::Double { main, alt } => {
-                word.trim().starts_with(main) || word.trim().starts_with(alt)
-            }
-            Self::Many { main, other } => {
-                if word.trim().starts_with(main) {
-                    return true;
-                }
-                for term in *other {
-                    if word.trim().starts_with(term) {
-                        return true;
-                    }
-                }
-                false
-            }
+        if self.is_excluded(word) {
+            return false;
         }
+        self.wordings().iter().any(|w| word.trim().starts_with(*w))
     }
 
     pub fn strip_term_from_word(&self, word: &str) -> String {
-        let ret = match self {
-            Self::Single(s) => word.replace(s, ""),
-            Self::Double { main, alt } => word.replace(main, "").replace(alt, ""),
-            Self::Many { main, other } => {
-                let mut ret = word.replace(main, "");
-                for term in *other {
-                    ret = ret.replace(term, "");
-                }
-                ret
-            }
-        };
+        let mut ret = word.to_string();
+        for wording in self.wordings() {
+            ret = ret.replace(wording, "");
+        }
         if let Some(pref) = ret.strip_prefix(":") {
             return pref.trim().to_string();
         }
         ret
     }
 
-    /// Finds the word I am looking for in a column of the table
-    fn find_term_in_column<'a>(&self, rows: &'a [[String; 2]]) -> Option<Cow<'a, str>> {
+    /// Finds the word I am looking for in a column of the table, returning the
+    /// second-column value as a ranked candidate tagged with its match strength.
+    fn find_term_in_column(&self, rows: &[[String; 2]], position: usize) -> Option<Candidate> {
         for row in rows {
             let [col1, col2] = row;
 
-            if self.matches(col1) && !col2.is_empty() {
-                return Some(col2.into());
+            if let Some(strength) = self.matches(col1) {
+                if !col2.is_empty() {
+                    let match_kind = match strength {
+                        MatchStrength::Exact => MatchKind::ExactColumn,
+                        MatchStrength::Fuzzy => MatchKind::FuzzyColumn,
+                    };
+                    return Some(Candidate {
+                        value: col2.clone(),
+                        match_kind,
+                        position,
+                    });
+                }
             }
         }
         None
     }
 }
 
-fn deep_search_term(search_space: &str, term: &str) -> bool {
-    let lower_term = term.to_lowercase();
-    let trimmed_lower_text = search_space.trim().to_lowercase();
+/// How strongly a term matched a candidate string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStrength {
+    Exact,
+    Fuzzy,
+}
 
-    trimmed_lower_text == lower_term
-        || trimmed_lower_text.split_whitespace().collect::<String>() == lower_term
-        || trimmed_lower_text.split(':').collect::<String>() == lower_term
+/// Typo-tolerant distance of `term` from `search_space` under the global typo
+/// budget, or `None` when the fuzzy tier is off or the candidate is out of
+/// budget. The exact/normalized tier is handled by the caller and always wins,
+/// so this only reports the fuzzy fallback.
+fn deep_search_term(search_space: &str, term: &str) -> Option<usize> {
+    crate::matcher::fuzzy_distance(search_space, term)
 }
 
-/// List of phrases in the doc that contains the info after the word
-/// The order here is as they appear in the doc
-const EXTRACT_SEARCH_TERMS_IN_ORDER: [Term; 10] = [
-    Term::Many {
-        main: "PROVINCE",
-        other: &["PROVINCE:", "Province", "Province:"],
-    },
-    Term::Many {
-        main: "DISTRICT",
-        other: &[
-            "DISTRICT:",
-            "District",
-            "District:",
-            "NAME OF DISTRICT",
-            "DISTRICT 1",
-        ],
-    },
-    Term::Many {
-        main: "SUBJECT",
-        other: &["SUBJECT:", "Subject", "Subject:"],
-    },
-    Term::Many {
-        main: "SCHOOL",
-        other: &[
-            "SCHOOL:",
-            "School",
-            "School:",
-            "List of Moderated Schools",
-            "The schools that were moderated are",
-            "The schools that were moderated are:",
-        ],
-    },
-    Term::Single("Areas of good practice / Innovation"),
-    Term::Many {
-        main: "IDENTIFICATION OF IRREGULARITIES",
-        other: &[
-            "IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
-            "SECTION F:  IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
-        ],
-    },
-    Term::Single("AREAS OF GOOD PRACTICE / INNOVATION"),
-    Term::Single("AREAS THAT REQUIRE INTERVENTION AND SUPPORT"),
-    Term::Double {
-        main: "RECOMMENDATIONS",
-        alt: "RECOMMENDATIONS FOR IMPROVEMENT",
-    },
-    Term::Single("CONCLUSION"),
-];
+/// A single value extracted for a term, together with enough provenance to rank
+/// it against the other places the same label turned up in the document.
+#[derive(Debug, Clone)]
+struct Candidate {
+    value: String,
+    match_kind: MatchKind,
+    position: usize,
+}
+
+/// Where and how strongly a [`Candidate`] matched, ordered best-first. Exact
+/// heading/column matches beat fuzzy ones, which beat loose paragraph hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    ExactHeading,
+    ExactColumn,
+    FuzzyHeading,
+    FuzzyColumn,
+    Paragraph,
+}
+
+impl Candidate {
+    /// Total ordering key: best match kind, then shorter value (cell values
+    /// beat long paragraph dumps), then earliest document position.
+    fn rank(&self) -> (MatchKind, usize, usize) {
+        (self.match_kind, self.value.chars().count(), self.position)
+    }
+}
+
+impl Term {
+    fn single(main: &'static str) -> Term {
+        Term {
+            kind: TermKind::Single(Cow::Borrowed(main)),
+            patterns: vec![],
+            exclude: vec![],
+        }
+    }
+
+    fn double(main: &'static str, alt: &'static str) -> Term {
+        Term {
+            kind: TermKind::Double {
+                main: Cow::Borrowed(main),
+                alt: Cow::Borrowed(alt),
+            },
+            patterns: vec![],
+            exclude: vec![],
+        }
+    }
+
+    fn many(main: &'static str, other: &[&'static str]) -> Term {
+        Term {
+            kind: TermKind::Many {
+                main: Cow::Borrowed(main),
+                other: other.iter().copied().map(Cow::Borrowed).collect(),
+            },
+            patterns: vec![],
+            exclude: vec![],
+        }
+    }
+
+    /// Attach the wordings that must not match this term, e.g. a table-of-
+    /// contents line that repeats the heading.
+    fn excluding(mut self, exclude: &[&'static str]) -> Term {
+        self.exclude = exclude.iter().copied().map(Cow::Borrowed).collect();
+        self
+    }
+}
+
+/// The phrases in the doc that contain the info after the word, in the order
+/// they appear in the document (which the output column order depends on).
+///
+/// This is the built-in dictionary. [`DocTables::try_into_extracted`] uses
+/// whatever term list its caller passes; nothing in this module builds terms
+/// from a [`crate::terms_config`] file.
+pub fn default_search_terms() -> Vec<Term> {
+    vec![
+        Term::many("PROVINCE", &["PROVINCE:", "Province", "Province:"]),
+        Term::many(
+            "DISTRICT",
+            &[
+                "DISTRICT:",
+                "District",
+                "District:",
+                "NAME OF DISTRICT",
+                "DISTRICT 1",
+            ],
+        ),
+        Term::many("SUBJECT", &["SUBJECT:", "Subject", "Subject:"]),
+        Term::many(
+            "SCHOOL",
+            &[
+                "SCHOOL:",
+                "School",
+                "School:",
+                "List of Moderated Schools",
+                "The schools that were moderated are",
+                "The schools that were moderated are:",
+            ],
+        ),
+        Term::single("Areas of good practice / Innovation"),
+        Term::many(
+            "IDENTIFICATION OF IRREGULARITIES",
+            &[
+                "IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
+                "SECTION F:  IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
+            ],
+        ),
+        Term::single("AREAS OF GOOD PRACTICE / INNOVATION"),
+        Term::single("AREAS THAT REQUIRE INTERVENTION AND SUPPORT"),
+        Term::double("RECOMMENDATIONS", "RECOMMENDATIONS FOR IMPROVEMENT")
+            .excluding(&["RECOMMENDATIONS TABLE OF CONTENTS"]),
+        Term::single("CONCLUSION"),
+    ]
+}
 
 #[derive(Debug)]
 pub struct DocTable {
@@ -234,77 +362,91 @@ pub struct DocTables {
 }
 
 impl DocTables {
-    pub fn try_into_extracted(self) -> ExtractedInfo {
+    pub fn try_into_extracted(self, terms: &[Term]) -> ExtractedInfo {
         let mut record = vec![];
-        for term in EXTRACT_SEARCH_TERMS_IN_ORDER {
-            let mut found_it = false;
+        for term in terms {
             debug!("Searching for term: {term}");
 
-            for DocTable { heading, rows } in &self.tables {
+            // Instead of returning on the first table/column/paragraph that
+            // matches, gather every candidate value for the term and rank them,
+            // so a repeated label (e.g. "District" in both a header table and a
+            // body paragraph) cannot have the wrong cell win by break order.
+            let mut candidates: Vec<Candidate> = vec![];
+
+            for (position, DocTable { heading, rows }) in self.tables.iter().enumerate() {
                 let Some(heading) = heading.as_ref() else {
                     debug!("No heading for table. Looking in column");
-                    if let Some(text) = term.find_term_in_column(rows) {
-                        found_it = true;
-                        record.push(text.to_string());
-                        break;
+                    if let Some(candidate) = term.find_term_in_column(rows, position) {
+                        candidates.push(candidate);
                     }
+                    continue;
+                };
 
+                let Some(strength) = term.matches(heading) else {
                     continue;
                 };
 
-                if term.matches(heading) {
-                    // With the district column on oral, the table heading has the word district
-                    // but the actual info is contained in the second column cell of the first row
-                    if first_column_contains_term(rows, &term)
-                        && let Some(text) = term.find_term_in_column(rows)
-                    {
-                        found_it = true;
-                        record.push(text.to_string());
-                        break;
-                    }
-                    record.push(
-                        rows.iter()
-                            .map(|x| {
-                                x.iter()
-                                    .filter(|x| !x.is_empty())
-                                    .map(ToString::to_string)
-                                    .collect::<String>()
-                            })
-                            .collect::<Vec<String>>()
-                            .join("\n"),
-                    );
-                    found_it = true;
-                    break;
-                }
-            }
-            if !found_it {
-                debug!("No heading for table. Looking in paragraphs");
-                if let Some(word) = self.find_in_paragraphs(&term) {
-                    record.push(word);
-                    debug!("Found term: {found_it}");
+                // With the district column on oral, the table heading has the word district
+                // but the actual info is contained in the second column cell of the first row
+                if first_column_contains_term(rows, term)
+                    && let Some(candidate) = term.find_term_in_column(rows, position)
+                {
+                    candidates.push(candidate);
                     continue;
                 }
-                debug!("Found term: {found_it}");
-                record.push("".to_string());
+
+                let value = rows
+                    .iter()
+                    .map(|x| {
+                        x.iter()
+                            .filter(|x| !x.is_empty())
+                            .map(ToString::to_string)
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                let match_kind = match strength {
+                    MatchStrength::Exact => MatchKind::ExactHeading,
+                    MatchStrength::Fuzzy => MatchKind::FuzzyHeading,
+                };
+                candidates.push(Candidate {
+                    value,
+                    match_kind,
+                    position,
+                });
             }
+
+            // Paragraph hits sort after table hits; offset their positions past
+            // the tables so document order still breaks ties sensibly.
+            debug!("Looking in paragraphs");
+            candidates.extend(self.find_in_paragraphs(term, self.tables.len()));
+
+            let best = candidates.into_iter().min_by(|a, b| a.rank().cmp(&b.rank()));
+            debug!("Best candidate for {term}: {best:?}");
+            record.push(best.map(|c| c.value).unwrap_or_default());
         }
         ExtractedInfo { record }
     }
 
-    fn find_in_paragraphs(&self, term: &Term) -> Option<String> {
-        for par in &self.paragraphs {
+    fn find_in_paragraphs(&self, term: &Term, position_base: usize) -> Vec<Candidate> {
+        let mut ret = vec![];
+        for (offset, par) in self.paragraphs.iter().enumerate() {
             if term.word_starts_with_term(par) {
-                return Some(term.strip_term_from_word(par));
+                ret.push(Candidate {
+                    value: term.strip_term_from_word(par),
+                    match_kind: MatchKind::Paragraph,
+                    position: position_base + offset,
+                });
             }
         }
-        None
+        ret
     }
 }
 
 fn first_column_contains_term(rows: &[[String; 2]], term: &Term) -> bool {
     for row in rows {
         let [col1, ..] = row;
-        if term.matches(col1) {
+        if term.matches(col1).is_some() {
             return true;
         }
     }
@@ -0,0 +1,230 @@
+//! A queryable inverted index over extracted records.
+//!
+//! The extractor emits a flat CSV, which is fine for a one-shot pass but gives
+//! no way to ask "which reports mention this phrase?" across a large archive
+//! without re-parsing every `.docx`. This module builds an inverted index from
+//! the [`ExtractedInfo`] records instead: each field is tokenised and every
+//! term is mapped to the documents and fields that contain it. The index
+//! serialises to a JSON sidecar (like the extraction [`crate::cache`]) so a
+//! `search` only touches that file, never the corpus.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ExtractedInfo;
+
+/// An inverted index over the extracted records of a corpus.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    /// term -> field name -> the document ids whose field contains the term.
+    postings: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+    /// document id -> field name -> full field text, kept so `search` can print
+    /// the matching snippet without re-reading the source document.
+    documents: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl SearchIndex {
+    /// Build an index from a slice of extracted records.
+    pub fn build(records: &[ExtractedInfo]) -> Self {
+        let mut index = SearchIndex::default();
+        for record in records {
+            let id = record.file_id();
+            for (field, text) in record.fields() {
+                for token in tokenize(&text) {
+                    index
+                        .postings
+                        .entry(token)
+                        .or_default()
+                        .entry(field.to_string())
+                        .or_default()
+                        .insert(id.clone());
+                }
+                index
+                    .documents
+                    .entry(id.clone())
+                    .or_default()
+                    .insert(field.to_string(), text);
+            }
+        }
+        index
+    }
+
+    /// Serialize the index to a JSON sidecar at `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).with_context(|| format!("Writing search index to {path:?}"))?;
+        Ok(())
+    }
+
+    /// Load an index previously written with [`Self::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Reading search index from {path:?}"))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Return the documents matching `query`, ranked by the number of distinct
+    /// query terms they contain and then by how many fields matched, so a
+    /// report that mentions the phrase in several sections outranks one that
+    /// only brushes it once. Ties break on the document id for determinism.
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        let terms = tokenize(query);
+        let mut hits: BTreeMap<String, DocHit> = BTreeMap::new();
+        for term in &terms {
+            let Some(fields) = self.postings.get(term) else {
+                continue;
+            };
+            for (field, docs) in fields {
+                for doc in docs {
+                    let hit = hits.entry(doc.clone()).or_default();
+                    hit.terms.insert(term.clone());
+                    hit.fields.insert(field.clone());
+                }
+            }
+        }
+
+        let mut results: Vec<SearchResult> = hits
+            .into_iter()
+            .map(|(file, hit)| {
+                let snippets = hit
+                    .fields
+                    .iter()
+                    .filter_map(|field| {
+                        let text = self.documents.get(&file)?.get(field)?;
+                        Some((field.clone(), snippet(text, &terms)))
+                    })
+                    .collect();
+                SearchResult {
+                    matched_terms: hit.terms.len(),
+                    matched_fields: hit.fields.len(),
+                    file,
+                    snippets,
+                }
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.matched_terms
+                .cmp(&a.matched_terms)
+                .then(b.matched_fields.cmp(&a.matched_fields))
+                .then(a.file.cmp(&b.file))
+        });
+        results
+    }
+}
+
+/// One ranked hit: a document and the snippets of each field that matched.
+#[derive(Debug)]
+pub struct SearchResult {
+    pub file: String,
+    pub matched_terms: usize,
+    pub matched_fields: usize,
+    pub snippets: Vec<(String, String)>,
+}
+
+/// Accumulates, for one document, which query terms and fields were hit.
+#[derive(Default)]
+struct DocHit {
+    terms: BTreeSet<String>,
+    fields: BTreeSet<String>,
+}
+
+/// Split text into lowercase alphanumeric tokens, dropping punctuation and
+/// runs of whitespace.
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// A short excerpt of `text` centred on the first query term it contains, so a
+/// search result shows where the match landed rather than the whole section.
+fn snippet(text: &str, terms: &[String]) -> String {
+    const WINDOW: usize = 80;
+    let lower = text.to_lowercase();
+    let start = terms
+        .iter()
+        .filter_map(|t| lower.find(t.as_str()))
+        .min()
+        .map(|pos| pos.saturating_sub(WINDOW / 2))
+        .unwrap_or(0);
+    // Snap to a char boundary so slicing a multi-byte document never panics.
+    let start = (start..=text.len())
+        .find(|i| text.is_char_boundary(*i))
+        .unwrap_or(0);
+    let excerpt: String = text[start..].chars().take(WINDOW).collect();
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if text[start..].chars().count() > WINDOW {
+        "…"
+    } else {
+        ""
+    };
+    format!("{prefix}{}{suffix}", excerpt.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{HeaderInfo, SectionG};
+
+    fn record(file: &str, areas: &str, recommendations: &str) -> ExtractedInfo {
+        ExtractedInfo {
+            header: HeaderInfo {
+                province: "Province".to_string(),
+                district: "District".to_string(),
+                school: "School".to_string(),
+                subject: None,
+            },
+            sectiong: SectionG {
+                areas_that_require_intervention_and_support: areas.to_string(),
+                recommendations: recommendations.to_string(),
+            },
+            file: PathBuf::from(file),
+        }
+    }
+
+    #[test]
+    fn snippet_snaps_to_char_boundary_without_panicking() {
+        // A run of two-byte chars before the match pushes the window start into
+        // the middle of a codepoint; snapping to a boundary must not panic.
+        let text = format!("{} target phrase here", "é".repeat(60));
+        let snip = snippet(&text, &["target".to_string()]);
+        assert!(snip.contains("target"));
+        assert!(snip.starts_with('…'));
+    }
+
+    #[test]
+    fn search_ranks_more_fields_higher() {
+        let records = [
+            record("one.docx", "safety concerns noted", "improve safety next term"),
+            record("two.docx", "general observations", "improve safety next term"),
+        ];
+        let index = SearchIndex::build(&records);
+        let results = index.search("safety");
+        assert_eq!(results.len(), 2);
+        // "one" matches the term in two fields, "two" in only one.
+        assert_eq!(results[0].file, "one.docx");
+        assert_eq!(results[0].matched_fields, 2);
+        assert_eq!(results[1].file, "two.docx");
+    }
+
+    #[test]
+    fn search_ranks_more_terms_higher() {
+        let records = [
+            record("one.docx", "safety and hygiene", "none"),
+            record("two.docx", "safety only", "none"),
+        ];
+        let index = SearchIndex::build(&records);
+        let results = index.search("safety hygiene");
+        assert_eq!(results[0].file, "one.docx");
+        assert_eq!(results[0].matched_terms, 2);
+        assert_eq!(results[1].matched_terms, 1);
+    }
+}
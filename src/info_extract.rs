@@ -1,5 +1,7 @@
 use crate::Block;
 use crate::DocBlocks;
+use crate::terms_config::{TermSpec, TermsConfig};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -15,14 +17,20 @@ use tracing::trace;
 
 #[derive(Debug)]
 pub struct ExtractedInfo {
-    pub header: HashMap<&'static str, String>,
-    pub body: HashMap<&'static str, String>,
+    pub header: HashMap<String, String>,
+    pub body: HashMap<String, String>,
 }
 
-const HEADER_WORDS: &[&str] = &["PROVINCE", "DISTRICT", "SCHOOL", "SUBJECT"];
+/// The built-in header words, used when no `--terms-config` is supplied.
+pub fn default_header_words() -> Vec<String> {
+    ["PROVINCE", "DISTRICT", "SCHOOL", "SUBJECT"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
 
-pub fn read_head(blocks: &DocBlocks) -> Result<HashMap<&'static str, String>> {
-    let Some(Block::Table(t)) = blocks.find_table_containing_one_of(HEADER_WORDS) else {
+pub fn read_head(blocks: &DocBlocks, header_words: &[String]) -> Result<HashMap<String, String>> {
+    let Some(Block::Table(t)) = blocks.find_table_containing_one_of(header_words) else {
         bail!("This doc does not have any of the header terms.");
     };
     let mut t = t.clone();
@@ -43,7 +51,7 @@ pub fn read_head(blocks: &DocBlocks) -> Result<HashMap<&'static str, String>> {
                 .to_uppercase()
                 .split(':')
                 .collect::<String>();
-            HEADER_WORDS.contains(&fixed.as_str())
+            header_words.iter().any(|w| *w == fixed)
         })
         .for_each(|x| {
             *x = x
@@ -56,33 +64,43 @@ pub fn read_head(blocks: &DocBlocks) -> Result<HashMap<&'static str, String>> {
 
     let mut ret = HashMap::new();
 
-    for word in HEADER_WORDS {
-        let Some(pos) = t.iter().position(|x| x == word) else {
-            ret.insert(*word, "".to_string());
+    for word in header_words {
+        // Prefer an exact cell, then fall back to the closest typo-tolerant
+        // candidate within the budget so an OCR slip like `SUBJET` still lands
+        // on `SUBJECT`. The nearest cell wins when several are within budget.
+        let pos = t.iter().position(|x| x == word).or_else(|| {
+            t.iter()
+                .enumerate()
+                .filter_map(|(i, cell)| crate::matcher::fuzzy_distance(cell, word).map(|d| (d, i)))
+                .min()
+                .map(|(_, i)| i)
+        });
+        let Some(pos) = pos else {
+            ret.insert(word.clone(), "".to_string());
             continue;
         };
         let val = t
             .get_mut(pos + 1)
             .expect("Next text to be the value of the word");
         let val = mem::take(val);
-        ret.insert(*word, val);
+        ret.insert(word.clone(), val);
     }
 
-    if ret.len() < 4 {
-        for word in HEADER_WORDS {
+    if ret.len() < header_words.len() {
+        for word in header_words {
             if ret.contains_key(word) {
                 continue;
             }
-            ret.insert(*word, String::new());
+            ret.insert(word.clone(), String::new());
         }
     }
     Ok(ret)
 }
 
-pub fn read_body_info(blocks: &DocBlocks) -> Result<HashMap<&'static str, String>> {
+pub fn read_body_info(blocks: &DocBlocks, terms: &[Term]) -> Result<HashMap<String, String>> {
     let mut ret = HashMap::new();
-    for term in EXTRACT_SEARCH_TERMS_IN_ORDER {
-        match blocks.find_term_table_text(&term) {
+    for term in terms {
+        match blocks.find_term_table_text(term) {
             Some(Block::Table(t)) => {
                 // Several sections might be in the table, so I need to scan again and slice it up.
                 // This will not be very performant, but should do for the small amount of times
@@ -93,8 +111,8 @@ pub fn read_body_info(blocks: &DocBlocks) -> Result<HashMap<&'static str, String
                 };
                 let mut first_term_after_me = None;
                 for (i, word) in t.iter().enumerate().skip(pos) {
-                    for term in EXTRACT_SEARCH_TERMS_IN_ORDER.iter().filter(|x| **x != term) {
-                        if !term.deep_matches(word) {
+                    for other in terms.iter().filter(|t| !std::ptr::eq(*t, term)) {
+                        if !other.deep_matches(word) {
                             continue;
                         }
                         first_term_after_me = Some(i);
@@ -132,18 +150,12 @@ impl ExtractedInfo {
         ret
     }
 
-    pub fn header_record() -> Vec<&'static str> {
-        let mut ret = vec![
-            "Province", "District", "School",
-            "Subject",
-            // "Areas That Require Intervention And Support",
-            // "Recommendations For Improvement",
-            // "File",
-        ];
-        for term in EXTRACT_SEARCH_TERMS_IN_ORDER.iter().take(TERM_LEN) {
+    pub fn header_record(header_words: &[String], terms: &[Term]) -> Vec<String> {
+        let mut ret: Vec<String> = header_words.to_vec();
+        for term in terms {
             ret.push(term.into_main());
         }
-        ret.push("File");
+        ret.push("File".to_string());
         ret
     }
 }
@@ -157,18 +169,16 @@ pub struct ExtractInfo {
     pub areas_of_good_practice_innovation: Option<String>,
 }
 
-const TERM_LEN: usize = 5;
-
-#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+#[derive(Eq, Hash, PartialEq, Clone, Debug)]
 pub enum Term {
-    Single(&'static str),
+    Single(Cow<'static, str>),
     Double {
-        main: &'static str,
-        alt: &'static str,
+        main: Cow<'static, str>,
+        alt: Cow<'static, str>,
     },
     Many {
-        main: &'static str,
-        other: &'static [&'static str],
+        main: Cow<'static, str>,
+        other: Vec<Cow<'static, str>>,
     },
 }
 
@@ -185,15 +195,56 @@ impl Display for Term {
 impl PartialEq<str> for Term {
     fn eq(&self, other: &str) -> bool {
         match self {
-            Self::Single(s) => *s == other,
-            Self::Double { main, alt } => *main == other || *alt == other,
-            Self::Many { main, other: o } => *main == other || o.contains(&other),
+            Self::Single(s) => s.as_ref() == other,
+            Self::Double { main, alt } => main.as_ref() == other || alt.as_ref() == other,
+            Self::Many { main, other: o } => {
+                main.as_ref() == other || o.iter().any(|t| t.as_ref() == other)
+            }
+        }
+    }
+}
+
+impl From<TermSpec> for Term {
+    fn from(spec: TermSpec) -> Self {
+        let TermSpec { main, mut aliases } = spec;
+        match aliases.len() {
+            0 => Term::Single(Cow::Owned(main)),
+            1 => Term::Double {
+                main: Cow::Owned(main),
+                alt: Cow::Owned(aliases.remove(0)),
+            },
+            _ => Term::Many {
+                main: Cow::Owned(main),
+                other: aliases.into_iter().map(Cow::Owned).collect(),
+            },
         }
     }
 }
 
 impl Term {
-    pub fn into_main(self) -> &'static str {
+    const fn single(main: &'static str) -> Term {
+        Term::Single(Cow::Borrowed(main))
+    }
+
+    const fn double(main: &'static str, alt: &'static str) -> Term {
+        Term::Double {
+            main: Cow::Borrowed(main),
+            alt: Cow::Borrowed(alt),
+        }
+    }
+
+    fn many(main: &'static str, other: &[&'static str]) -> Term {
+        Term::Many {
+            main: Cow::Borrowed(main),
+            other: other.iter().copied().map(Cow::Borrowed).collect(),
+        }
+    }
+
+    pub fn into_main(&self) -> String {
+        self.main().to_string()
+    }
+
+    pub fn main(&self) -> &str {
         match self {
             Self::Single(s) => s,
             Self::Double { main, .. } => main,
@@ -212,7 +263,7 @@ impl Term {
                 if deep_search_term(search_space, main) {
                     return true;
                 }
-                for term in *other {
+                for term in other {
                     if deep_search_term(search_space, term) {
                         return true;
                     }
@@ -223,11 +274,7 @@ impl Term {
     }
 
     pub fn is(&self, main_word: &str) -> bool {
-        match self {
-            Self::Single(s) => *s == main_word,
-            Self::Double { main, .. } => *main == main_word,
-            Self::Many { main, .. } => *main == main_word,
-        }
+        self.main() == main_word
     }
 }
 
@@ -235,26 +282,53 @@ fn deep_search_term(search_space: &str, term: &str) -> bool {
     let lower_term = term.to_lowercase();
     let trimmed_lower_text = search_space.trim().to_lowercase();
 
-    trimmed_lower_text == lower_term
+    if trimmed_lower_text == lower_term
         || trimmed_lower_text.split_whitespace().collect::<String>() == lower_term
         || trimmed_lower_text.split(':').collect::<String>() == lower_term
+    {
+        return true;
+    }
+    // Fall back to the typo-tolerant tier; a no-op unless `--max-typos` is set.
+    crate::matcher::fuzzy_distance(search_space, term).is_some()
 }
 
-/// List of phrases in the doc that contains the info after the word
-/// The order here is as they appear in the doc
-const EXTRACT_SEARCH_TERMS_IN_ORDER: [Term; TERM_LEN] = [
-    Term::Many {
-        main: "IDENTIFICATION OF IRREGULARITIES",
-        other: &[
-            "IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
-            "SECTION F:  IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
-        ],
-    },
-    Term::Single("AREAS OF GOOD PRACTICE / INNOVATION"),
-    Term::Single("AREAS THAT REQUIRE INTERVENTION AND SUPPORT"),
-    Term::Double {
-        main: "RECOMMENDATIONS",
-        alt: "RECOMMENDATIONS FOR IMPROVEMENT",
-    },
-    Term::Single("CONCLUSION"),
-];
+/// List of phrases in the doc that contains the info after the word.
+/// The order here is as they appear in the doc and is used when no
+/// `--terms-config` overrides it.
+pub fn default_terms() -> Vec<Term> {
+    vec![
+        Term::many(
+            "IDENTIFICATION OF IRREGULARITIES",
+            &[
+                "IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
+                "SECTION F:  IDENTIFICATION OF NON-COMPLIANCE / IRREGULARITIES",
+            ],
+        ),
+        Term::single("AREAS OF GOOD PRACTICE / INNOVATION"),
+        Term::single("AREAS THAT REQUIRE INTERVENTION AND SUPPORT"),
+        Term::double("RECOMMENDATIONS", "RECOMMENDATIONS FOR IMPROVEMENT"),
+        Term::single("CONCLUSION"),
+    ]
+}
+
+/// Build the header words and ordered terms that extraction should use, taking
+/// them from a loaded [`TermsConfig`] when present and otherwise falling back to
+/// the built-in defaults.
+pub fn resolve(config: Option<&TermsConfig>) -> (Vec<String>, Vec<Term>) {
+    match config {
+        Some(config) => {
+            let headers = if config.headers.is_empty() {
+                default_header_words()
+            } else {
+                config.headers.clone()
+            };
+            let terms = if config.terms.is_empty() {
+                default_terms()
+            } else {
+                config.terms.iter().cloned().map(Term::from).collect()
+            };
+            (headers, terms)
+        }
+        None => (default_header_words(), default_terms()),
+    }
+}
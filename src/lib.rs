@@ -1,17 +1,26 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use anyhow::{bail, Result};
 use derive_builder::Builder;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
-use serde::Serialize;
-use tracing::{debug, info, trace};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, trace, warn};
+
+pub mod cache;
+pub mod index;
+pub mod matcher;
+pub mod terms_config;
 
 use docx_rust::document::{
     Paragraph, ParagraphContent, RunContent, TableCell, TableCellContent, TableRowContent,
 };
 use docx_rust::{document::BodyContent, Docx};
 
+use crate::matcher::{DifferenceMatcher, IncludeMatcher, Matcher, NormalizedMatcher, UnionMatcher};
+use crate::terms_config::TermsConfig;
+
 pub fn extract_first_table_first_row(docx: &Docx) {
     // let mut table_counter = 0;
     // Iterate through all blocks in the document
@@ -240,7 +249,7 @@ pub fn read_cell_text(buf: &mut Vec<u8>, reader: &mut Reader<&[u8]>) -> Result<S
     read_run_text(buf, reader)
 }
 
-#[derive(Builder, Debug, Serialize)]
+#[derive(Builder, Debug, Serialize, Deserialize)]
 #[builder_struct_attr(derive(Debug))]
 pub struct HeaderInfo {
     pub province: String,
@@ -249,8 +258,264 @@ pub struct HeaderInfo {
     pub subject: Option<String>,
 }
 
-pub fn read_header_info(buf: &mut Vec<u8>, reader: &mut Reader<&[u8]>) -> Result<HeaderInfo> {
+/// One canonical output field and the cell labels that introduce it on the live
+/// extraction path. The built-in labels can be extended at runtime: a
+/// `--terms-config` entry whose key names the field contributes its aliases, so
+/// a document that labels the cell differently (`Provinsie`, say) is still
+/// recognised without recompiling.
+struct LabelSet {
+    key: &'static str,
+    wordings: Vec<String>,
+    /// Glob (`glob:` prefixed) or regex (`re:` prefixed) patterns supplied by a
+    /// config alias, compiled into [`IncludeMatcher`]s alongside the literal
+    /// wordings so an operator can match a whole family of labels at once.
+    patterns: Vec<String>,
+    /// Wordings that look like this field's label but must be rejected, e.g. a
+    /// table-of-contents line repeating the heading.
+    exclude: Vec<String>,
+}
+
+impl LabelSet {
+    fn new(key: &'static str, wordings: &[&str]) -> Self {
+        Self {
+            key,
+            wordings: wordings.iter().map(|w| w.to_string()).collect(),
+            patterns: vec![],
+            exclude: vec![],
+        }
+    }
+
+    /// Attach the wordings that must not match this label.
+    fn excluding(mut self, exclude: &[&str]) -> Self {
+        self.exclude = exclude.iter().map(|w| w.to_string()).collect();
+        self
+    }
+
+    fn union(words: &[String]) -> UnionMatcher {
+        UnionMatcher::new(
+            words
+                .iter()
+                .map(|w| Box::new(NormalizedMatcher::new(w.as_str())) as Box<dyn Matcher>)
+                .collect(),
+        )
+    }
+
+    /// The accepted side of the matcher: every literal wording as a
+    /// [`NormalizedMatcher`] plus every configured glob/regex as an
+    /// [`IncludeMatcher`]. Patterns that fail to compile are logged and skipped.
+    fn include_matcher(&self) -> UnionMatcher {
+        let mut inner: Vec<Box<dyn Matcher>> = self
+            .wordings
+            .iter()
+            .map(|w| Box::new(NormalizedMatcher::new(w.as_str())) as Box<dyn Matcher>)
+            .collect();
+        for token in &self.patterns {
+            if let Some(matcher) = compile_pattern(token) {
+                inner.push(matcher);
+            }
+        }
+        UnionMatcher::new(inner)
+    }
+
+    /// Compile the accepted wordings and patterns into a union matcher,
+    /// subtracting the exclusions, so detection reuses the same composable
+    /// [`crate::matcher`] pieces as the rest of section matching.
+    fn matcher(&self) -> DifferenceMatcher {
+        DifferenceMatcher::new(
+            Box::new(self.include_matcher()),
+            Box::new(Self::union(&self.exclude)),
+        )
+    }
+
+    /// Whether `cell` is one of this field's explicit exclusions.
+    fn is_excluded(&self, cell: &str) -> bool {
+        Self::union(&self.exclude).matches(cell)
+    }
+
+    /// Grade how well `cell` matches this field, or `None` if it doesn't. The
+    /// exact/normalized tier is tried first; only then, and only when the
+    /// operator passed `--max-typos`, does the typo-tolerant fallback run, so an
+    /// OCR slip like `RECOMENDATIONS` still matches but ranks below a clean hit.
+    fn classify(&self, cell: &str) -> Option<Strength> {
+        if self.matcher().matches(cell) {
+            return Some(Strength::EXACT);
+        }
+        // Exclusions bind the fuzzy tier too: a rejected wording must not come
+        // back in as a near-miss.
+        if self.is_excluded(cell) {
+            return None;
+        }
+        self.wordings
+            .iter()
+            .filter_map(|w| crate::matcher::fuzzy_distance(cell, w))
+            .min()
+            .map(Strength::fuzzy)
+    }
+}
+
+/// How well a cell matched a label, ordered so the smallest value is the best
+/// candidate: an exact/normalized hit beats any fuzzy one, and among fuzzy hits
+/// a smaller edit distance wins. Used to keep the closest label for a field when
+/// several cells could lay claim to it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Strength {
+    /// 0 for an exact/normalized match, 1 for a typo-tolerant one.
+    tier: u8,
+    /// Edit distance within the tier (always 0 for an exact match).
+    distance: usize,
+}
+
+impl Strength {
+    const EXACT: Self = Self {
+        tier: 0,
+        distance: 0,
+    };
+
+    fn fuzzy(distance: usize) -> Self {
+        Self { tier: 1, distance }
+    }
+}
+
+/// Compile a `glob:`/`re:` prefixed config token into an [`IncludeMatcher`],
+/// returning `None` (with a warning) for an unprefixed token or one that fails
+/// to compile, so a single bad pattern never aborts extraction.
+fn compile_pattern(token: &str) -> Option<Box<dyn Matcher>> {
+    let compiled = if let Some(glob) = token.strip_prefix("glob:") {
+        IncludeMatcher::glob(glob)
+    } else if let Some(re) = token.strip_prefix("re:") {
+        IncludeMatcher::regex(re)
+    } else {
+        return None;
+    };
+    match compiled {
+        Ok(matcher) => Some(Box::new(matcher)),
+        Err(e) => {
+            warn!("Ignoring invalid pattern {token:?}: {e}");
+            None
+        }
+    }
+}
+
+/// Route a config wording to its field's literal-label list or, when it carries
+/// a `glob:`/`re:` prefix, its pattern list.
+fn add_wording(set: &mut LabelSet, wording: String) {
+    if wording.starts_with("glob:") || wording.starts_with("re:") {
+        set.patterns.push(wording);
+    } else {
+        set.wordings.push(wording);
+    }
+}
+
+/// Collapse whitespace, drop `:` and fold case so a label compares equal
+/// regardless of the incidental spacing and punctuation in the source cell.
+fn normalize_label(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<String>()
+        .replace(':', "")
+}
+
+/// Extend each field's labels with the aliases of any config term whose key
+/// names that field. Terms that don't name a known field are left for the other
+/// label set (headers vs sections); anything matching neither is reported by
+/// [`warn_unrecognised_config`], which the binaries call once at startup.
+fn apply_aliases(sets: &mut [LabelSet], config: &TermsConfig) {
+    for spec in &config.terms {
+        let main = normalize_label(&spec.main);
+        if let Some(set) = sets.iter_mut().find(|s| normalize_label(s.key) == main) {
+            add_wording(set, spec.main.clone());
+            for alias in &spec.aliases {
+                add_wording(set, alias.clone());
+            }
+        }
+    }
+}
+
+/// The canonical field keys the live extractor recognises, normalized for
+/// comparison. Output columns are fixed by [`HeaderInfo`] and [`SectionG`], so a
+/// config term only ever *widens* how one of these fields is labelled; it can
+/// never introduce a new column.
+fn known_field_keys() -> Vec<String> {
+    let defaults = TermsConfig::default();
+    header_labels(&defaults)
+        .iter()
+        .chain(section_labels(&defaults).iter())
+        .map(|set| normalize_label(set.key))
+        .collect()
+}
+
+/// Warn about config entries the fixed-schema extractor cannot honour so a typo
+/// or a genuinely new field is visible rather than a silent no-op: a `[terms]`
+/// key that names no known field, and any `[headers]` word (the header fields
+/// are fixed, so that section is informational only). Call once after loading a
+/// `--terms-config`.
+pub fn warn_unrecognised_config(config: &TermsConfig) {
+    let known = known_field_keys();
+    for spec in &config.terms {
+        if !known.contains(&normalize_label(&spec.main)) {
+            warn!(
+                "Config term {:?} names no extractor field; its aliases are ignored. \
+                 Known fields: Province, District, School, Subject, \
+                 \"AREAS THAT REQUIRE INTERVENTION AND SUPPORT\", RECOMMENDATIONS.",
+                spec.main
+            );
+        }
+    }
+    if !config.headers.is_empty() {
+        warn!(
+            "[headers] entries are not consulted by this extractor; the header fields are \
+             fixed (Province, District, School, Subject). Widen a field with a [terms] \
+             alias instead, e.g. `Province = Provinsie`."
+        );
+    }
+}
+
+/// The header fields and their accepted cell labels, extended by `config`.
+fn header_labels(config: &TermsConfig) -> Vec<LabelSet> {
+    let mut sets = vec![
+        LabelSet::new("Province", &["Province"]),
+        LabelSet::new("District", &["District", "District/Region"]),
+        LabelSet::new("School", &["School"]),
+        LabelSet::new("Subject", &["Subject"]),
+    ];
+    apply_aliases(&mut sets, config);
+    sets
+}
+
+fn set_header_field(results: &mut HeaderInfoBuilder, key: &str, value: String) {
+    match key {
+        "Province" => {
+            results.province(value);
+        }
+        "District" => {
+            results.district(value);
+        }
+        "School" => {
+            results.school(value);
+        }
+        "Subject" => {
+            results.subject(Some(value));
+        }
+        _ => {}
+    }
+}
+
+/// Read the header table, classifying each label cell against [`header_labels`]
+/// and keeping the closest match per field: an exact/normalized hit beats a
+/// fuzzy one, and a closer fuzzy hit beats a farther one (see [`Strength`]),
+/// with ties resolved in favour of the earlier cell. This disambiguates a label
+/// that repeats within the header table. It does not scan body paragraphs — the
+/// streaming reader only visits the header and Section G tables — so a duplicate
+/// that lives outside those tables is out of scope for the live path.
+pub fn read_header_info(
+    buf: &mut Vec<u8>,
+    reader: &mut Reader<&[u8]>,
+    config: &TermsConfig,
+) -> Result<HeaderInfo> {
+    let labels = header_labels(config);
     let mut results = HeaderInfoBuilder::create_empty();
+    let mut best: HashMap<&'static str, Strength> = HashMap::new();
     let mut protection_counter = 0;
     loop {
         protection_counter += 1;
@@ -264,25 +529,19 @@ pub fn read_header_info(buf: &mut Vec<u8>, reader: &mut Reader<&[u8]>) -> Result
                 colval = t.clone();
             }
             debug!("{colval}, Loop: {i}, protcount: {protection_counter} <- HeaderInfo");
-            match colval.as_str() {
-                "Province" => {
-                    let prov = read_cell_text(buf, reader)?;
-                    debug!("{prov} <- Province");
-                    results.province(prov);
-                }
-                "District" | "District/Region" => {
-                    let dis = read_cell_text(buf, reader)?;
-                    results.district(dis);
-                }
-                "School" => {
-                    let sc = read_cell_text(buf, reader)?;
-                    results.school(sc);
+            if let Some((set, strength)) =
+                labels.iter().find_map(|s| Some((s, s.classify(&colval)?)))
+            {
+                let value = read_cell_text(buf, reader)?;
+                // Keep the closest label for each field: only overwrite when this
+                // cell matched more strongly than whatever filled it before.
+                if best.get(set.key).is_none_or(|prev| strength < *prev) {
+                    best.insert(set.key, strength);
+                    debug!("{value} <- {}", set.key);
+                    set_header_field(&mut results, set.key, value);
                 }
-                "Subject" => {
-                    let sub = read_cell_text(buf, reader)?;
-                    results.subject(Some(sub));
-                }
-                other => debug!("{other} text found"),
+            } else {
+                debug!("{colval} text found");
             }
         }
         if protection_counter > 14
@@ -362,25 +621,58 @@ pub fn read_run_text_until(
     Ok(res.join(""))
 }
 
-pub fn read_sectiong_info(buf: &mut Vec<u8>, reader: &mut Reader<&[u8]>) -> Result<SectionG> {
+/// The section fields and their accepted labels, extended by `config`.
+fn section_labels(config: &TermsConfig) -> Vec<LabelSet> {
+    let mut sets = vec![
+        LabelSet::new(
+            "AREAS THAT REQUIRE INTERVENTION AND SUPPORT",
+            &["AREAS THAT REQUIRE INTERVENTION AND SUPPORT"],
+        ),
+        LabelSet::new(
+            "RECOMMENDATIONS",
+            &["RECOMMENDATIONS", "RECOMMENDATIONS FOR IMPROVEMENT"],
+        )
+        .excluding(&["RECOMMENDATIONS TABLE OF CONTENTS"]),
+    ];
+    apply_aliases(&mut sets, config);
+    sets
+}
+
+fn set_section_field(results: &mut SectionGBuilder, key: &str, value: String) {
+    match key {
+        "AREAS THAT REQUIRE INTERVENTION AND SUPPORT" => {
+            results.areas_that_require_intervention_and_support(value);
+        }
+        "RECOMMENDATIONS" => {
+            results.recommendations(value);
+        }
+        _ => {}
+    }
+}
+
+pub fn read_sectiong_info(
+    buf: &mut Vec<u8>,
+    reader: &mut Reader<&[u8]>,
+    config: &TermsConfig,
+) -> Result<SectionG> {
+    let labels = section_labels(config);
     let mut results = SectionGBuilder::create_empty();
+    let mut best: HashMap<&'static str, Strength> = HashMap::new();
     let mut protection_counter = 0;
     read_to_sectiong_table(buf, reader)?;
     loop {
         protection_counter += 1;
         let t = read_row_first_cell_text(buf, reader).unwrap();
         debug!("{t} <- sectiong table cell");
-        match t.as_str() {
-            "AREAS THAT REQUIRE INTERVENTION AND SUPPORT:" => {
-                let area = read_cell_text(buf, reader)?;
-                results.areas_that_require_intervention_and_support(area);
+        if let Some((set, strength)) = labels.iter().find_map(|s| Some((s, s.classify(&t)?))) {
+            let value = read_cell_text(buf, reader)?;
+            // Keep the closest label for each field (see read_header_info).
+            if best.get(set.key).is_none_or(|prev| strength < *prev) {
+                best.insert(set.key, strength);
+                set_section_field(&mut results, set.key, value);
             }
-            "RECOMMENDATIONS:" => {
-                let rec = read_cell_text(buf, reader)?;
-                results.recommendations(rec);
-            }
-
-            other => debug!("{other} text found"),
+        } else {
+            debug!("{t} text found");
         }
         if protection_counter > 3
             && !(results
@@ -400,7 +692,7 @@ pub fn read_sectiong_info(buf: &mut Vec<u8>, reader: &mut Reader<&[u8]>) -> Resu
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExtractedInfo {
     pub header: HeaderInfo,
     pub sectiong: SectionG,
@@ -449,9 +741,30 @@ impl ExtractedInfo {
             "File",
         ]
     }
+
+    /// The document id used to identify this record in the search index: its
+    /// source path.
+    pub fn file_id(&self) -> String {
+        self.file.to_str().unwrap_or_default().to_string()
+    }
+
+    /// The searchable `(field name, text)` pairs of this record, in column
+    /// order. The labels match [`Self::header_record`] minus the trailing
+    /// `File` column, which is the document id rather than content.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let [province, district, school, subject, areas, recommendations, _file] = self.as_record();
+        vec![
+            ("Province", province.to_string()),
+            ("District", district.to_string()),
+            ("School", school.to_string()),
+            ("Subject", subject.to_string()),
+            ("Areas That Require Intervention And Support", areas.to_string()),
+            ("Recommendations For Improvement", recommendations.to_string()),
+        ]
+    }
 }
 
-#[derive(Builder, Debug, Serialize)]
+#[derive(Builder, Debug, Serialize, Deserialize)]
 #[builder_struct_attr(derive(Debug))]
 pub struct SectionG {
     pub areas_that_require_intervention_and_support: String,
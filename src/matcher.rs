@@ -0,0 +1,318 @@
+//! Composable text matchers behind section detection.
+//!
+//! The live extractor ([`crate::read_header_info`] / [`crate::read_sectiong_info`])
+//! used to hardcode each label and only compare it with exact, case-sensitive
+//! equality. That is brittle across the many district-by-district wording
+//! variants, so label detection instead delegates to a compiled [`Matcher`]
+//! assembled from small composable pieces:
+//!
+//! * [`NormalizedMatcher`] reproduces the historical exact/normalized compare.
+//! * [`IncludeMatcher`] compiles a user glob or regex pattern.
+//! * [`UnionMatcher`] ORs several matchers together (replacing the
+//!   `Term::Many` fan-out).
+//! * [`DifferenceMatcher`] subtracts an exclusion, so `RECOMMENDATIONS` can
+//!   match while `RECOMMENDATIONS TABLE OF CONTENTS` is explicitly rejected.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use regex::Regex;
+
+/// Anything that can decide whether a candidate string matches.
+pub trait Matcher {
+    fn matches(&self, text: &str) -> bool;
+}
+
+/// Matches with the normalized-equality rules that `deep_search_term` has
+/// always used: exact, whitespace-collapsed, or colon-stripped (case folded).
+pub struct NormalizedMatcher {
+    term: String,
+}
+
+impl NormalizedMatcher {
+    pub fn new(term: impl Into<String>) -> Self {
+        Self {
+            term: term.into().to_lowercase(),
+        }
+    }
+}
+
+impl Matcher for NormalizedMatcher {
+    fn matches(&self, text: &str) -> bool {
+        let trimmed = text.trim().to_lowercase();
+        trimmed == self.term
+            || trimmed.split_whitespace().collect::<String>() == self.term
+            || trimmed.split(':').collect::<String>() == self.term
+    }
+}
+
+/// Matches a glob or regex pattern supplied by the operator.
+pub struct IncludeMatcher {
+    re: Regex,
+}
+
+impl IncludeMatcher {
+    /// Compile a regex pattern (case insensitive), anchored to the whole string
+    /// like [`glob`](Self::glob) so a section label is matched in full rather
+    /// than as an incidental substring of a longer cell.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            re: Regex::new(&format!("(?i)^(?:{pattern})$"))?,
+        })
+    }
+
+    /// Compile a shell-style glob (`*`, `?`) anchored to the whole string.
+    pub fn glob(pattern: &str) -> Result<Self> {
+        let mut re = String::from("(?i)^");
+        for ch in pattern.chars() {
+            match ch {
+                '*' => re.push_str(".*"),
+                '?' => re.push('.'),
+                other => re.push_str(&regex::escape(&other.to_string())),
+            }
+        }
+        re.push('$');
+        Ok(Self {
+            re: Regex::new(&re)?,
+        })
+    }
+}
+
+impl Matcher for IncludeMatcher {
+    fn matches(&self, text: &str) -> bool {
+        self.re.is_match(text.trim())
+    }
+}
+
+/// Matches when any of the wrapped matchers matches.
+#[derive(Default)]
+pub struct UnionMatcher {
+    inner: Vec<Box<dyn Matcher>>,
+}
+
+impl UnionMatcher {
+    pub fn new(inner: Vec<Box<dyn Matcher>>) -> Self {
+        Self { inner }
+    }
+
+    pub fn push(&mut self, matcher: Box<dyn Matcher>) {
+        self.inner.push(matcher);
+    }
+}
+
+impl Matcher for UnionMatcher {
+    fn matches(&self, text: &str) -> bool {
+        self.inner.iter().any(|m| m.matches(text))
+    }
+}
+
+/// Matches the `include` matcher but rejects anything the `exclude` matcher
+/// also matches.
+pub struct DifferenceMatcher {
+    include: Box<dyn Matcher>,
+    exclude: Box<dyn Matcher>,
+}
+
+impl DifferenceMatcher {
+    pub fn new(include: Box<dyn Matcher>, exclude: Box<dyn Matcher>) -> Self {
+        Self { include, exclude }
+    }
+}
+
+impl Matcher for DifferenceMatcher {
+    fn matches(&self, text: &str) -> bool {
+        self.include.matches(text) && !self.exclude.matches(text)
+    }
+}
+
+/// Operator-selected ceiling on the number of typos tolerated when a candidate
+/// is matched against a target word. A budget of `0` (the default) disables the
+/// fuzzy tier, leaving label matching to [`NormalizedMatcher`] — case-,
+/// whitespace- and colon-insensitive equality. Note this is *not* the original
+/// byte-exact `match colval.as_str()`: that comparison was intentionally
+/// replaced, so at `0` a cell reading `PROVINCE` still matches a `Province`
+/// label. `--max-typos N` only adds the extra typo-tolerant tier on top.
+///
+/// This is process-global state set once from a binary's `main` — the CLIs are
+/// the only callers — rather than threaded through each call.
+static MAX_TYPOS: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the global typo budget consulted by [`fuzzy_distance`]. `0` disables the
+/// fuzzy tier entirely.
+pub fn set_max_typos(budget: usize) {
+    MAX_TYPOS.store(budget, Ordering::Relaxed);
+}
+
+/// The current global typo budget.
+pub fn max_typos() -> usize {
+    MAX_TYPOS.load(Ordering::Relaxed)
+}
+
+/// Fuzzy-compare `text` against `target` under the global typo budget, returning
+/// the edit distance when the two are within the length-scaled, budget-capped
+/// threshold and `None` when fuzzy matching is off or the candidate is too far.
+/// Callers try exact/normalized matching first; this is the fallback tier only.
+pub fn fuzzy_distance(text: &str, target: &str) -> Option<usize> {
+    let budget = max_typos();
+    if budget == 0 {
+        return None;
+    }
+    let text = normalize_for_fuzzy(text);
+    let target = normalize_for_fuzzy(target);
+    let threshold = typo_budget(target.chars().count(), budget);
+    bounded_damerau_levenshtein(&text, &target, threshold)
+}
+
+/// Allowed edit distance for a word of `len` characters: scaled by length so
+/// short labels must still match exactly, then capped at the operator's `max`
+/// budget. Zero typos for four characters or fewer, one for five to eight, two
+/// beyond that.
+fn typo_budget(len: usize, max: usize) -> usize {
+    let scaled = match len {
+        0..=4 => 0,
+        5..=8 => 1,
+        _ => 2,
+    };
+    scaled.min(max)
+}
+
+/// Lowercase, drop `:` and punctuation hugging each word, and collapse internal
+/// whitespace to single spaces so the edit distance only reflects real typos.
+fn normalize_for_fuzzy(text: &str) -> String {
+    text.to_lowercase()
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| c.is_ascii_punctuation())
+                .replace(':', "")
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Damerau-Levenshtein distance between `a` and `b`, counting an adjacent
+/// transposition (a swap of two neighbouring characters, the classic OCR/typing
+/// slip) as a single edit, and returning `None` as soon as the distance is
+/// known to exceed `budget`. The length difference is a lower bound on the
+/// distance, so a pair whose lengths differ by more than `budget` is rejected
+/// before any work; the per-row minimum is checked after each row so a hopeless
+/// comparison aborts without filling the whole table.
+fn bounded_damerau_levenshtein(a: &str, b: &str, budget: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+    if n.abs_diff(m) > budget {
+        return None;
+    }
+    if n == 0 {
+        return (m <= budget).then_some(m);
+    }
+    if m == 0 {
+        return (n <= budget).then_some(n);
+    }
+
+    // Three rolling rows: the previous-previous row is what the transposition
+    // case looks back to.
+    let mut prev_prev = vec![0usize; m + 1];
+    let mut prev: Vec<usize> = (0..=m).collect();
+    let mut cur = vec![0usize; m + 1];
+    for i in 1..=n {
+        cur[0] = i;
+        let mut row_min = cur[0];
+        for j in 1..=m {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(prev_prev[j - 2] + 1);
+            }
+            cur[j] = best;
+            row_min = row_min.min(cur[j]);
+        }
+        if row_min > budget {
+            return None;
+        }
+        std::mem::swap(&mut prev_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    (prev[m] <= budget).then_some(prev[m])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_counts_substitutions_within_budget() {
+        assert_eq!(bounded_damerau_levenshtein("province", "privince", 2), Some(1));
+        assert_eq!(bounded_damerau_levenshtein("province", "province", 2), Some(0));
+    }
+
+    #[test]
+    fn distance_counts_adjacent_transposition_as_one_edit() {
+        // A classic typing slip: swapped neighbours cost a single edit, not two.
+        assert_eq!(
+            bounded_damerau_levenshtein("recommendations", "recommendaitons", 2),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn distance_rejects_candidates_past_budget() {
+        // A substitution plus a transposition (distance two) with a budget of
+        // one: the per-row minimum aborts it.
+        assert_eq!(bounded_damerau_levenshtein("school", "scbool", 1), Some(1));
+        assert_eq!(bounded_damerau_levenshtein("school", "scbolo", 1), None);
+    }
+
+    #[test]
+    fn distance_rejects_on_length_difference_before_any_work() {
+        // Lengths differ by more than the budget: a lower bound rules it out.
+        assert_eq!(bounded_damerau_levenshtein("district", "dist", 2), None);
+    }
+
+    #[test]
+    fn glob_matches_whole_string_case_insensitively() {
+        let m = IncludeMatcher::glob("DISTRICT*").unwrap();
+        assert!(m.matches("district"));
+        assert!(m.matches("DISTRICT/REGION"));
+        // Anchored to the whole string, not an incidental substring.
+        assert!(!m.matches("THE DISTRICT"));
+    }
+
+    #[test]
+    fn regex_matches_whole_string_case_insensitively() {
+        let m = IncludeMatcher::regex("area[s]? of .*").unwrap();
+        assert!(m.matches("Area of improvement"));
+        assert!(m.matches("AREAS OF NON-COMPLIANCE"));
+        assert!(!m.matches("improvement areas of"));
+    }
+
+    #[test]
+    fn union_ors_and_difference_subtracts() {
+        let union = UnionMatcher::new(vec![
+            Box::new(NormalizedMatcher::new("recommendations")),
+            Box::new(NormalizedMatcher::new("recommendations for improvement")),
+        ]);
+        let diff = DifferenceMatcher::new(
+            Box::new(union),
+            Box::new(NormalizedMatcher::new("recommendations table of contents")),
+        );
+        assert!(diff.matches("RECOMMENDATIONS"));
+        assert!(diff.matches("Recommendations For Improvement"));
+        assert!(!diff.matches("RECOMMENDATIONS TABLE OF CONTENTS"));
+    }
+
+    #[test]
+    fn typo_budget_scales_with_length_and_caps_at_max() {
+        // Zero typos for four characters or fewer.
+        assert_eq!(typo_budget(4, 5), 0);
+        // One for five to eight.
+        assert_eq!(typo_budget(5, 5), 1);
+        assert_eq!(typo_budget(8, 5), 1);
+        // Two beyond that.
+        assert_eq!(typo_budget(12, 5), 2);
+        // Never more than the operator's own ceiling.
+        assert_eq!(typo_budget(12, 1), 1);
+        assert_eq!(typo_budget(12, 0), 0);
+    }
+}
@@ -0,0 +1,245 @@
+//! Runtime configuration for the header words and section terms that drive
+//! extraction.
+//!
+//! `HEADER_WORDS` and the `Term` array in the early `info_extract`/`extraction`
+//! prototypes used to be compile-time `const`s, so supporting a new document
+//! layout meant recompiling. Those prototypes (and their JSON term loader) are
+//! not part of the shipping crate — only `cache`, `index`, `matcher` and this
+//! module are declared in `lib.rs`. The format landed as INI rather than the
+//! TOML/JSON the original ticket floated: it keeps `%include`/`%unset`
+//! composition and ordered sections in a single dependency-free parser, and the
+//! two library config types were consolidated onto it (the JSON `TermSpec`
+//! loader was dropped). This module parses that INI config at runtime:
+//!
+//! ```ini
+//! [headers]
+//! PROVINCE
+//! DISTRICT
+//!
+//! [terms]
+//! RECOMMENDATIONS = RECOMMENDATIONS FOR IMPROVEMENT
+//! SCHOOL = School, School:, List of Moderated Schools
+//! DISTRICT = District, glob:DISTRICT *, re:district/\s*region
+//!
+//! %include sites/limpopo.conf
+//! %unset SUBJECT
+//! ```
+//!
+//! Two directives let several files compose: `%include path` pulls in another
+//! config (resolved relative to the including file) and `%unset KEY` drops an
+//! inherited header word or term so a site-specific override can remove a
+//! default. Declaration order is preserved because `read_body_info` slices
+//! sections in the order the terms appear.
+//!
+//! Note the live extractor has a **fixed output schema** ([`crate::HeaderInfo`]
+//! / [`crate::SectionG`]): a `[terms]` entry only widens how an existing field
+//! is labelled when its key names that field (e.g. `Province = Provinsie`), and
+//! the `[headers]` section is not consulted. A config cannot add a new output
+//! column at runtime. [`crate::warn_unrecognised_config`] reports entries that
+//! fall outside this schema so they are not a silent no-op.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+/// One `main = alt1, alt2, ...` entry. The aliases are the accepted wordings
+/// that map onto the canonical `main` column header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermSpec {
+    pub main: String,
+    pub aliases: Vec<String>,
+}
+
+/// The header words and ordered section terms parsed from a config file.
+#[derive(Debug, Clone, Default)]
+pub struct TermsConfig {
+    pub headers: Vec<String>,
+    pub terms: Vec<TermSpec>,
+}
+
+impl TermsConfig {
+    /// Parse a config file, recursively resolving `%include` directives.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut config = TermsConfig::default();
+        let mut visiting = HashSet::new();
+        config.parse_file(path.as_ref(), &mut visiting)?;
+        if config.headers.is_empty() && config.terms.is_empty() {
+            bail!(
+                "Terms config {:?} defined neither headers nor terms",
+                path.as_ref()
+            );
+        }
+        Ok(config)
+    }
+
+    /// Remove an inherited header word or term by its key (case sensitive).
+    fn unset(&mut self, key: &str) {
+        self.headers.retain(|h| h != key);
+        self.terms.retain(|t| t.main != key);
+    }
+
+    /// Insert (or, if the key already exists, replace in place) a term so that
+    /// declaration order stays stable across overrides.
+    fn upsert_term(&mut self, spec: TermSpec) {
+        if let Some(existing) = self.terms.iter_mut().find(|t| t.main == spec.main) {
+            *existing = spec;
+        } else {
+            self.terms.push(spec);
+        }
+    }
+
+    fn upsert_header(&mut self, word: String) {
+        if !self.headers.contains(&word) {
+            self.headers.push(word);
+        }
+    }
+
+    fn parse_file(&mut self, path: &Path, visiting: &mut HashSet<PathBuf>) -> Result<()> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Resolving terms config {path:?}"))?;
+        if !visiting.insert(canonical.clone()) {
+            bail!("Include cycle detected at {canonical:?}");
+        }
+
+        let section_re = Regex::new(r"^\[([^\]]+)\]\s*$").unwrap();
+        let item_re = Regex::new(r"^([^=]+?)\s*=\s*(.*)$").unwrap();
+        let comment_re = Regex::new(r"^\s*[;#]").unwrap();
+        let include_re = Regex::new(r"^%include\s+(.+?)\s*$").unwrap();
+        let unset_re = Regex::new(r"^%unset\s+(.+?)\s*$").unwrap();
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Reading terms config {path:?}"))?;
+
+        let mut section = Section::None;
+        for raw_line in contents.lines() {
+            let line = raw_line.trim();
+            if line.is_empty() || comment_re.is_match(line) {
+                continue;
+            }
+
+            if let Some(caps) = include_re.captures(line) {
+                let included = self.resolve_relative(path, &caps[1]);
+                debug!("Including terms config {included:?}");
+                self.parse_file(&included, visiting)?;
+                continue;
+            }
+
+            if let Some(caps) = unset_re.captures(line) {
+                debug!("Unsetting {}", &caps[1]);
+                self.unset(caps[1].trim());
+                continue;
+            }
+
+            if let Some(caps) = section_re.captures(line) {
+                section = Section::from_name(caps[1].trim());
+                continue;
+            }
+
+            match section {
+                Section::Headers => self.upsert_header(line.to_string()),
+                Section::Terms => {
+                    let caps = item_re.captures(line).with_context(|| {
+                        format!("Malformed term in {path:?}: {raw_line:?}")
+                    })?;
+                    let main = caps[1].trim().to_string();
+                    let aliases = caps[2]
+                        .split(',')
+                        .map(|a| a.trim().to_string())
+                        .filter(|a| !a.is_empty())
+                        .collect();
+                    self.upsert_term(TermSpec { main, aliases });
+                }
+                Section::None => {
+                    bail!("Directive outside of a section in {path:?}: {raw_line:?}")
+                }
+            }
+        }
+
+        visiting.remove(&canonical);
+        Ok(())
+    }
+
+    /// Resolve an `%include` target relative to the file that referenced it.
+    fn resolve_relative(&self, including: &Path, target: &str) -> PathBuf {
+        let target = Path::new(target);
+        if target.is_absolute() {
+            return target.to_path_buf();
+        }
+        match including.parent() {
+            Some(dir) => dir.join(target),
+            None => target.to_path_buf(),
+        }
+    }
+}
+
+enum Section {
+    None,
+    Headers,
+    Terms,
+}
+
+impl Section {
+    fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "headers" => Section::Headers,
+            "terms" => Section::Terms,
+            _ => Section::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// A clean, process-unique scratch directory for a test's config files.
+    fn tmpdir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("terms_config_{}_{tag}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn include_composes_and_unset_removes() {
+        let dir = tmpdir("compose");
+        fs::write(dir.join("base.conf"), "[terms]\nSCHOOL = School\nSUBJECT = Subject\n").unwrap();
+        fs::write(
+            dir.join("site.conf"),
+            "%include base.conf\n[terms]\nPROVINCE = Provinsie\n%unset SUBJECT\n",
+        )
+        .unwrap();
+
+        let config = TermsConfig::load(dir.join("site.conf")).unwrap();
+        // SCHOOL inherited, SUBJECT dropped by %unset, PROVINCE added — order kept.
+        let mains: Vec<&str> = config.terms.iter().map(|t| t.main.as_str()).collect();
+        assert_eq!(mains, ["SCHOOL", "PROVINCE"]);
+        let school = config.terms.iter().find(|t| t.main == "SCHOOL").unwrap();
+        assert_eq!(school.aliases, vec!["School".to_string()]);
+    }
+
+    #[test]
+    fn detects_include_cycle() {
+        let dir = tmpdir("cycle");
+        fs::write(dir.join("a.conf"), "[terms]\nSCHOOL = School\n%include b.conf\n").unwrap();
+        fs::write(dir.join("b.conf"), "%include a.conf\n").unwrap();
+
+        let err = TermsConfig::load(dir.join("a.conf")).unwrap_err();
+        assert!(err.to_string().contains("cycle"), "got: {err}");
+    }
+
+    #[test]
+    fn malformed_term_line_errors() {
+        let dir = tmpdir("malformed");
+        fs::write(dir.join("bad.conf"), "[terms]\nthis line has no equals\n").unwrap();
+
+        let err = TermsConfig::load(dir.join("bad.conf")).unwrap_err();
+        assert!(err.to_string().contains("Malformed term"), "got: {err}");
+    }
+}